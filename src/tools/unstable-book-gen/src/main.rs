@@ -18,10 +18,11 @@ use tidy::features::{Feature, Features, collect_lib_features, collect_lang_featu
 use tidy::unstable_book::{collect_unstable_feature_names, collect_unstable_book_section_file_names,
                           PATH_STR, LANG_FEATURES_DIR, LIB_FEATURES_DIR};
 use std::collections::HashSet;
-use std::io::Write;
+use std::io::{Read, Write};
 use std::fs::{self, File};
 use std::env;
 use std::path::Path;
+use std::process;
 
 /// A helper macro to `unwrap` a result except also print out details like:
 ///
@@ -35,31 +36,79 @@ macro_rules! t {
     })
 }
 
-fn generate_stub_issue(path: &Path, name: &str, issue: u32) {
-    let mut file = t!(File::create(path));
-    t!(file.write_fmt(format_args!(include_str!("stub-issue.md"),
-                                   name = name,
-                                   issue = issue)));
+/// Whether this run should write generated files to disk, or merely check
+/// that the files already on disk match what would be generated.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Generate,
+    Check,
 }
 
-fn generate_stub_no_issue(path: &Path, name: &str) {
-    let mut file = t!(File::create(path));
-    t!(file.write_fmt(format_args!(include_str!("stub-no-issue.md"),
-                                   name = name)));
+fn stub_issue_content(name: &str, issue: u32) -> String {
+    format!(include_str!("stub-issue.md"), name = name, issue = issue)
+}
+
+fn stub_no_issue_content(name: &str) -> String {
+    format!(include_str!("stub-no-issue.md"), name = name)
+}
+
+/// Reads `path` into a string, returning `None` if it doesn't exist or
+/// isn't valid UTF-8.
+fn read_to_string(path: &Path) -> Option<String> {
+    let mut file = match File::open(path) {
+        Ok(f) => f,
+        Err(_) => return None,
+    };
+    let mut content = String::new();
+    match file.read_to_string(&mut content) {
+        Ok(_) => Some(content),
+        Err(_) => None,
+    }
+}
+
+/// In `Generate` mode, writes `content` to `path`. In `Check` mode, compares
+/// `content` against what's already at `path` and records a problem in
+/// `report` if the file is missing or out of date, without touching disk.
+fn write_or_check(mode: Mode, report: &mut Vec<String>, path: &Path, content: &str) {
+    match mode {
+        Mode::Generate => {
+            let mut file = t!(File::create(path));
+            t!(file.write_all(content.as_bytes()));
+        }
+        Mode::Check => {
+            match read_to_string(path) {
+                Some(ref existing) if existing == content => {}
+                Some(_) => report.push(format!("stale: {}", path.display())),
+                None => report.push(format!("missing: {}", path.display())),
+            }
+        }
+    }
+}
+
+fn generate_stub_issue(mode: Mode, report: &mut Vec<String>, path: &Path, name: &str, issue: u32) {
+    write_or_check(mode, report, path, &stub_issue_content(name, issue));
+}
+
+fn generate_stub_no_issue(mode: Mode, report: &mut Vec<String>, path: &Path, name: &str) {
+    write_or_check(mode, report, path, &stub_no_issue_content(name));
 }
 
 fn hset_to_summary_str(hset: HashSet<String>, dir: &str
 ) -> String {
-    hset
+    let mut names = hset
+        .into_iter()
+        .map(|n| (n.replace('_', "-"), n))
+        .collect::<Vec<_>>();
+    names.sort();
+
+    names
         .iter()
-        .map(|ref n| format!("    - [{}]({}/{}.md)",
-                                      n,
-                                      dir,
-                                      n.replace('_', "-")))
+        .map(|&(ref slug, ref n)| format!("    - [{}]({}/{}.md)", n, dir, slug))
         .fold("".to_owned(), |s, a| s + &a + "\n")
 }
 
-fn generate_summary(path: &Path, lang_features: &Features, lib_features: &Features) {
+fn generate_summary(mode: Mode, report: &mut Vec<String>, path: &Path,
+                    lang_features: &Features, lib_features: &Features) {
     let compiler_flags = collect_unstable_book_section_file_names(
         &path.join("compiler-flags"));
 
@@ -74,12 +123,12 @@ fn generate_summary(path: &Path, lang_features: &Features, lib_features: &Featur
     let lib_features_str = hset_to_summary_str(unstable_lib_features,
                                                LIB_FEATURES_DIR);
 
-    let mut file = t!(File::create(&path.join("SUMMARY.md")));
-    t!(file.write_fmt(format_args!(include_str!("SUMMARY.md"),
-                                   compiler_flags = compiler_flags_str,
-                                   language_features = lang_features_str,
-                                   library_features = lib_features_str)));
+    let content = format!(include_str!("SUMMARY.md"),
+                          compiler_flags = compiler_flags_str,
+                          language_features = lang_features_str,
+                          library_features = lib_features_str);
 
+    write_or_check(mode, report, &path.join("SUMMARY.md"), &content);
 }
 
 fn has_valid_tracking_issue(f: &Feature) -> bool {
@@ -91,19 +140,50 @@ fn has_valid_tracking_issue(f: &Feature) -> bool {
     false
 }
 
-fn generate_unstable_book_files(src :&Path, out: &Path, features :&Features) {
+fn generate_unstable_book_files(mode: Mode, report: &mut Vec<String>,
+                                src: &Path, out: &Path, features: &Features) {
     let unstable_features = collect_unstable_feature_names(features);
     let unstable_section_file_names = collect_unstable_book_section_file_names(src);
-    t!(fs::create_dir_all(&out));
+    if mode == Mode::Generate {
+        t!(fs::create_dir_all(&out));
+    }
     for feature_name in &unstable_features - &unstable_section_file_names {
         let file_name = format!("{}.md", feature_name.replace('_', "-"));
         let out_file_path = out.join(&file_name);
         let feature = &features[&feature_name];
 
         if has_valid_tracking_issue(&feature) {
-            generate_stub_issue(&out_file_path, &feature_name, feature.tracking_issue.unwrap());
+            generate_stub_issue(mode, report, &out_file_path, &feature_name,
+                                feature.tracking_issue.unwrap());
         } else {
-            generate_stub_no_issue(&out_file_path, &feature_name);
+            generate_stub_no_issue(mode, report, &out_file_path, &feature_name);
+        }
+    }
+
+    report_orphaned_docs(mode, report, src, &unstable_features, &unstable_section_file_names);
+}
+
+/// Warns about (or, in `Check` mode, records as errors) doc sections whose
+/// feature has since been stabilized or removed, so they no longer show up
+/// in `unstable_features`. These pages aren't deleted automatically, since
+/// they may contain hand-written prose worth preserving or moving elsewhere,
+/// but they shouldn't be left to linger unnoticed.
+fn report_orphaned_docs(mode: Mode, report: &mut Vec<String>, src: &Path,
+                        unstable_features: &HashSet<String>,
+                        unstable_section_file_names: &HashSet<String>) {
+    let mut orphaned = (unstable_section_file_names - unstable_features)
+        .into_iter()
+        .collect::<Vec<_>>();
+    orphaned.sort();
+
+    for feature_name in orphaned {
+        let file_name = format!("{}.md", feature_name.replace('_', "-"));
+        let path = src.join(&file_name);
+        let message = format!("orphaned doc page for stabilized/removed feature: {}",
+                              path.display());
+        match mode {
+            Mode::Generate => println!("warning: {}", message),
+            Mode::Check => report.push(message),
         }
     }
 }
@@ -128,22 +208,46 @@ fn main() {
     let src_path = Path::new(&src_path_str);
     let dest_path = Path::new(&dest_path_str).join("src");
 
+    let check_arg = env::args_os().skip(3).next().map_or(false, |a| a == "--check");
+    let check_env = env::var_os("UNSTABLE_BOOK_GEN_CHECK").is_some();
+    let mode = if check_arg || check_env {
+        Mode::Check
+    } else {
+        Mode::Generate
+    };
+
     let lang_features = collect_lang_features(src_path);
     let mut bad = false;
     let lib_features = collect_lib_features(src_path, &mut bad, &lang_features);
 
     let doc_src_path = src_path.join(PATH_STR);
 
-    t!(fs::create_dir_all(&dest_path));
+    if mode == Mode::Generate {
+        t!(fs::create_dir_all(&dest_path));
+    }
+
+    let mut report = Vec::new();
 
-    generate_unstable_book_files(&doc_src_path.join(LANG_FEATURES_DIR),
+    generate_unstable_book_files(mode, &mut report,
+                                 &doc_src_path.join(LANG_FEATURES_DIR),
                                  &dest_path.join(LANG_FEATURES_DIR),
                                  &lang_features);
-    generate_unstable_book_files(&doc_src_path.join(LIB_FEATURES_DIR),
+    generate_unstable_book_files(mode, &mut report,
+                                 &doc_src_path.join(LIB_FEATURES_DIR),
                                  &dest_path.join(LIB_FEATURES_DIR),
                                  &lib_features);
 
-    copy_recursive(&doc_src_path, &dest_path);
+    if mode == Mode::Generate {
+        copy_recursive(&doc_src_path, &dest_path);
+    }
 
-    generate_summary(&dest_path, &lang_features, &lib_features);
+    generate_summary(mode, &mut report, &dest_path, &lang_features, &lib_features);
+
+    if mode == Mode::Check && !report.is_empty() {
+        println!("unstable book is out of date:");
+        for problem in &report {
+            println!("  {}", problem);
+        }
+        process::exit(1);
+    }
 }
\ No newline at end of file